@@ -4,6 +4,9 @@ pub struct Config {
     pub chars: bool,
     pub words: bool,
     pub lines: bool,
+    pub max_line_length: bool,
+    pub jobs: Option<usize>,
+    pub no_decompress: bool,
 }
 
 impl Config {
@@ -21,6 +24,9 @@ impl Config {
         if self.lines {
             vec.push("lines");
         }
+        if self.max_line_length {
+            vec.push("max_line_length");
+        }
         vec
     }
 }
@@ -1,5 +1,8 @@
 use crate::config::Config;
 use bytecount;
+use flate2::read::MultiGzDecoder;
+use memmap2::Mmap;
+use rayon::prelude::*;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Read};
 use std::path::PathBuf;
@@ -7,14 +10,159 @@ use utf8::{BufReadDecoder, BufReadDecoderError};
 
 const BUFFER_SIZE: usize = 1048576;
 
-// count bytes, words, lines
-pub(crate) fn binary<T: Read>(mut reader: BufReader<T>) -> (usize, usize, usize) {
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+// sniffs `path` for gzip compression and wraps it in a MultiGzDecoder, unless no_decompress opts out
+pub(crate) fn open_possibly_gzipped(path: &PathBuf, no_decompress: bool) -> Result<Box<dyn Read>, Error> {
+    let file = File::open(path).map_err(Error::Io)?;
+    if no_decompress {
+        return Ok(Box::new(file));
+    }
+    let has_gz_extension = path.extension().map_or(false, |ext| ext == "gz");
+    let mut peeked = BufReader::with_capacity(BUFFER_SIZE, file);
+    let has_gz_magic = peeked
+        .fill_buf()
+        .map_err(Error::Io)?
+        .starts_with(&GZIP_MAGIC);
+    if has_gz_extension || has_gz_magic {
+        Ok(Box::new(MultiGzDecoder::new(peeked)))
+    } else {
+        Ok(Box::new(peeked))
+    }
+}
+
+// Whether `path` would be treated as gzip-compressed by `open_possibly_gzipped`.
+pub(crate) fn is_gzip(path: &PathBuf) -> bool {
+    if path.extension().map_or(false, |ext| ext == "gz") {
+        return true;
+    }
+    match File::open(path) {
+        Ok(file) => BufReader::with_capacity(2, file)
+            .fill_buf()
+            .map(|buf| buf.starts_with(&GZIP_MAGIC))
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+const SNIFF_SIZE: usize = 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ContentType {
+    Utf8,
+    Utf16,
+    Binary,
+}
+
+impl ContentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentType::Utf8 => "utf8",
+            ContentType::Utf16 => "utf16",
+            ContentType::Binary => "binary",
+        }
+    }
+}
+
+// classifies a buffer like content_inspector does, forgiving a trailing incomplete sequence only when not at_eof
+pub(crate) fn inspect_content_type(buf: &[u8], at_eof: bool) -> ContentType {
+    if buf.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return ContentType::Utf8;
+    }
+    if buf.starts_with(&[0xFF, 0xFE]) || buf.starts_with(&[0xFE, 0xFF]) {
+        return ContentType::Utf16;
+    }
+    if buf.contains(&0) {
+        return ContentType::Binary;
+    }
+    match std::str::from_utf8(buf) {
+        Ok(_) => ContentType::Utf8,
+        Err(e) => {
+            if !at_eof && buf.len() - e.valid_up_to() <= 4 && e.error_len().is_none() {
+                ContentType::Utf8
+            } else {
+                ContentType::Binary
+            }
+        }
+    }
+}
+
+fn detect_content_type(path: &PathBuf, no_decompress: bool) -> Result<ContentType, Error> {
+    let mut reader = open_possibly_gzipped(path, no_decompress)?;
+    let mut buf = [0u8; SNIFF_SIZE];
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]).map_err(Error::Io)? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(inspect_content_type(&buf[..len], len < buf.len()))
+}
+
+// advances running_width by c's display width; at each '\n' folds it into max_width and resets it to 0
+fn update_line_width(c: char, running_width: &mut usize, max_width: &mut usize) {
+    match c {
+        '\n' => {
+            if *running_width > *max_width {
+                *max_width = *running_width;
+            }
+            *running_width = 0;
+        }
+        '\t' => *running_width = *running_width / 8 * 8 + 8,
+        c => *running_width += char_display_width(c),
+    }
+}
+
+fn char_display_width(c: char) -> usize {
+    let cp = c as u32;
+    if is_zero_width(cp) {
+        0
+    } else if is_east_asian_wide(cp) {
+        2
+    } else {
+        1
+    }
+}
+
+fn is_zero_width(cp: u32) -> bool {
+    matches!(cp,
+        0x0300..=0x036F
+        | 0x200B..=0x200F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+    )
+}
+
+fn is_east_asian_wide(cp: u32) -> bool {
+    matches!(cp,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1FAFF
+        | 0x20000..=0x3FFFD
+    )
+}
+
+// count bytes, words, lines, max line length
+pub(crate) fn binary<T: Read>(mut reader: BufReader<T>) -> (usize, usize, usize, usize) {
     let (mut bytes, mut words, mut lines) = (0, 0, 0);
     let mut in_word = false;
+    let (mut running_width, mut max_width) = (0, 0);
     loop {
         let buffer = match reader.fill_buf() {
             Ok(b) => b,
-            Err(_) => return (0, 0, 0), // TODO
+            Err(_) => return (0, 0, 0, 0), // TODO
         };
         let len = buffer.len();
         if len == 0 {
@@ -29,19 +177,24 @@ pub(crate) fn binary<T: Read>(mut reader: BufReader<T>) -> (usize, usize, usize)
             } else {
                 in_word = true;
             }
+            update_line_width(b as char, &mut running_width, &mut max_width);
         }
         reader.consume(len);
     }
     if in_word {
         words += 1;
     }
-    (bytes, words, lines)
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    (bytes, words, lines, max_width)
 }
 
-// count bytes, chars, words, lines
-pub(crate) fn utf8<T: Read>(reader: BufReader<T>) -> (usize, usize, usize, usize) {
+// count bytes, chars, words, lines, max line length
+pub(crate) fn utf8<T: Read>(reader: BufReader<T>) -> (usize, usize, usize, usize, usize) {
     let (mut bytes, mut chars, mut words, mut lines) = (0, 0, 0, 0);
     let mut in_word = false;
+    let (mut running_width, mut max_width) = (0, 0);
     let mut decoder = BufReadDecoder::new(reader);
     loop {
         if let Some(res) = decoder.next_strict() {
@@ -57,9 +210,10 @@ pub(crate) fn utf8<T: Read>(reader: BufReader<T>) -> (usize, usize, usize, usize
                         } else {
                             in_word = true;
                         }
+                        update_line_width(c, &mut running_width, &mut max_width);
                     }
                 }
-                Err(_) => return (0, 0, 0, 0), // TODO fail over to binary file
+                Err(_) => return (0, 0, 0, 0, 0), // TODO fail over to binary file
             }
         } else {
             break;
@@ -68,16 +222,20 @@ pub(crate) fn utf8<T: Read>(reader: BufReader<T>) -> (usize, usize, usize, usize
     if in_word {
         words += 1;
     }
-    (bytes, chars, words, lines)
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    (bytes, chars, words, lines, max_width)
 }
 
-// count bytes, lines
-pub(crate) fn hyperscreamingcount<T: Read>(mut reader: BufReader<T>) -> (usize, usize) {
+// count bytes, lines, max line length
+pub(crate) fn hyperscreamingcount<T: Read>(mut reader: BufReader<T>) -> (usize, usize, usize) {
     let (mut bytes, mut lines) = (0, 0);
+    let (mut running_width, mut max_width) = (0, 0);
     loop {
         let buffer = match reader.fill_buf() {
             Ok(b) => b,
-            Err(_) => return (0, 0), // TODO
+            Err(_) => return (0, 0, 0), // TODO
         };
         let len = buffer.len();
         if len == 0 {
@@ -85,14 +243,57 @@ pub(crate) fn hyperscreamingcount<T: Read>(mut reader: BufReader<T>) -> (usize,
         }
         bytes += len;
         lines += bytecount::count(buffer, b'\n');
+        for &b in buffer {
+            update_line_width(b as char, &mut running_width, &mut max_width);
+        }
         reader.consume(len);
     }
-    (bytes, lines)
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    (bytes, lines, max_width)
+}
+
+// below this size, syscall/mmap setup costs more than a streaming BufReader pass
+const MMAP_THRESHOLD: u64 = 8 * 1024 * 1024;
+
+pub(crate) fn mmap_worthy(path: &PathBuf) -> bool {
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.len() >= MMAP_THRESHOLD)
+        .unwrap_or(false)
+}
+
+// byte/word/line/max-line-length scan directly over an mmap'd region, no BufReader copies
+pub(crate) fn mmap_binary(data: &[u8]) -> (usize, usize, usize, usize) {
+    let bytes = data.len();
+    let lines = bytecount::count(data, b'\n');
+    let mut words = 0;
+    let mut in_word = false;
+    let (mut running_width, mut max_width) = (0, 0);
+    for &b in data {
+        if b.is_ascii_whitespace() {
+            if in_word {
+                words += 1;
+                in_word = false;
+            }
+        } else {
+            in_word = true;
+        }
+        update_line_width(b as char, &mut running_width, &mut max_width);
+    }
+    if in_word {
+        words += 1;
+    }
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    (bytes, words, lines, max_width)
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Context<'pathbuf> {
     File { path: &'pathbuf PathBuf },
+    Total,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -102,35 +303,83 @@ pub struct Count<'pathbuf> {
     pub chars: Option<usize>,
     pub words: Option<usize>,
     pub lines: Option<usize>,
+    pub content_type: Option<ContentType>,
+    pub max_line_length: Option<usize>,
 }
 
 impl<'pathbuf> Count<'pathbuf> {
-    pub fn to_counts_vec(&self) -> Vec<usize> {
+    // One slot per column `config` has enabled, in header order, so every row
+    // has the same width even when a field is `None` (e.g. `chars` on a
+    // binary file).
+    pub fn to_counts_vec(&self, config: &Config) -> Vec<Option<usize>> {
         let mut vec = Vec::new();
-        if let Some(bytes) = self.bytes {
-            vec.push(bytes)
+        if config.bytes {
+            vec.push(self.bytes);
         }
-        if let Some(chars) = self.chars {
-            vec.push(chars)
+        if config.chars {
+            vec.push(self.chars);
         }
-        if let Some(words) = self.words {
-            vec.push(words)
+        if config.words {
+            vec.push(self.words);
         }
-        if let Some(lines) = self.lines {
-            vec.push(lines)
+        if config.lines {
+            vec.push(self.lines);
+        }
+        if config.max_line_length {
+            vec.push(self.max_line_length);
         }
         vec
     }
 
-    pub fn to_str_vec(&self) -> Vec<String> {
+    pub fn to_str_vec(&self, config: &Config) -> Vec<String> {
         let mut vec = vec![self.groupname()];
-        vec.extend(self.to_counts_vec().iter().map(ToString::to_string));
+        vec.extend(
+            self.to_counts_vec(config)
+                .iter()
+                .map(|count| count.map_or_else(|| "-".to_string(), |c| c.to_string())),
+        );
         vec
     }
 
     pub fn groupname(&self) -> String {
         match self.context {
             Context::File { path } => path.to_string_lossy().to_string(),
+            Context::Total => "total".to_string(),
+        }
+    }
+
+    // sums bytes/chars/words/lines and maxes max_line_length, like GNU wc's trailing total line
+    pub fn total(counts: &[&Count<'pathbuf>]) -> Count<'pathbuf> {
+        let mut bytes = None;
+        let mut chars = None;
+        let mut words = None;
+        let mut lines = None;
+        let mut max_line_length = None;
+        for count in counts {
+            if let Some(b) = count.bytes {
+                bytes = Some(bytes.unwrap_or(0) + b);
+            }
+            if let Some(c) = count.chars {
+                chars = Some(chars.unwrap_or(0) + c);
+            }
+            if let Some(w) = count.words {
+                words = Some(words.unwrap_or(0) + w);
+            }
+            if let Some(l) = count.lines {
+                lines = Some(lines.unwrap_or(0) + l);
+            }
+            if let Some(m) = count.max_line_length {
+                max_line_length = Some(max_line_length.unwrap_or(0).max(m));
+            }
+        }
+        Count {
+            context: Context::Total,
+            bytes,
+            chars,
+            words,
+            lines,
+            max_line_length,
+            content_type: None,
         }
     }
 }
@@ -144,13 +393,15 @@ pub enum Error {
 fn binary_file<'a>(
     path: &'a PathBuf,
     only_bytes: bool,
-) -> Result<(usize, usize, usize, usize), Error> {
-    let file = File::open(path).unwrap();
-    let mut reader = BufReader::with_capacity(BUFFER_SIZE, file);
+    no_decompress: bool,
+) -> Result<(usize, usize, usize, usize, usize), Error> {
+    let reader = open_possibly_gzipped(path, no_decompress)?;
+    let mut reader = BufReader::with_capacity(BUFFER_SIZE, reader);
     let mut lines = 0;
     let mut bytes = 0;
     let mut words = 0;
     let mut in_word = false;
+    let (mut running_width, mut max_width) = (0, 0);
 
     loop {
         let buffer = match reader.fill_buf() {
@@ -175,22 +426,30 @@ fn binary_file<'a>(
                 } else {
                     in_word = true;
                 }
+                update_line_width(b as char, &mut running_width, &mut max_width);
             }
         }
         reader.consume(len);
     }
-    Ok((bytes, 0, words, lines))
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    Ok((bytes, 0, words, lines, max_width))
 }
 
-fn utf8_file<'a>(path: &'a PathBuf) -> Result<(usize, usize, usize, usize), Error> {
-    let file = File::open(path).unwrap();
-    let reader = BufReader::with_capacity(BUFFER_SIZE, file);
+fn utf8_file<'a>(
+    path: &'a PathBuf,
+    no_decompress: bool,
+) -> Result<(usize, usize, usize, usize, usize), Error> {
+    let reader = open_possibly_gzipped(path, no_decompress)?;
+    let reader = BufReader::with_capacity(BUFFER_SIZE, reader);
     let mut decoder = BufReadDecoder::new(reader);
     let mut lines = 0;
     let mut bytes = 0;
     let mut words = 0;
     let mut chars = 0;
     let mut in_word = false;
+    let (mut running_width, mut max_width) = (0, 0);
     loop {
         if let Some(res) = decoder.next_strict() {
             match res {
@@ -209,6 +468,7 @@ fn utf8_file<'a>(path: &'a PathBuf) -> Result<(usize, usize, usize, usize), Erro
                         } else {
                             in_word = true;
                         }
+                        update_line_width(c, &mut running_width, &mut max_width);
                     }
                 }
                 Err(e) => {
@@ -224,25 +484,73 @@ fn utf8_file<'a>(path: &'a PathBuf) -> Result<(usize, usize, usize, usize), Erro
             break;
         }
     }
-    Ok((bytes, chars, words, lines))
+    if running_width > max_width {
+        max_width = running_width;
+    }
+    Ok((bytes, chars, words, lines, max_width))
+}
+
+// whether path/config make the mmap_binary path safe: big enough and not a gzip stream
+fn mmap_eligible(path: &PathBuf, config: &Config) -> bool {
+    mmap_worthy(path) && (config.no_decompress || !is_gzip(path))
+}
+
+fn mmap_file(path: &PathBuf) -> Result<(usize, usize, usize, usize), Error> {
+    let file = File::open(path).map_err(Error::Io)?;
+    let mmap = unsafe { Mmap::map(&file).map_err(Error::Io)? };
+    Ok(mmap_binary(&mmap))
 }
 
 pub fn files<'a>(paths: &'a Vec<PathBuf>, config: &Config) -> Vec<Result<Count<'a>, Error>> {
-    paths.into_iter().map(|path| file(path, config)).collect()
+    let count_all = || paths.par_iter().map(|path| file(path, config)).collect();
+    match config.jobs {
+        Some(jobs) => rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .unwrap()
+            .install(count_all),
+        None => count_all(),
+    }
 }
 
 pub fn file<'a>(path: &'a PathBuf, config: &Config) -> Result<Count<'a>, Error> {
-    let (bytes, chars, words, lines) = if config.chars {
-        utf8_file(path)?
+    // -L's display width needs decoded Unicode scalars, not raw bytes, so it
+    // rides the same UTF-8 path as -c even when -c itself wasn't requested.
+    let needs_decoding = config.chars || config.max_line_length;
+    let content_type = if needs_decoding {
+        Some(detect_content_type(path, config.no_decompress)?)
     } else {
-        binary_file(path, !config.words && !config.lines)?
+        None
+    };
+    let utf8_decodable = content_type == Some(ContentType::Utf8);
+    let (bytes, chars, words, lines, max_line_length) = if needs_decoding && utf8_decodable {
+        utf8_file(path, config.no_decompress)?
+    } else if mmap_eligible(path, config) {
+        let (bytes, words, lines, max_line_length) = mmap_file(path)?;
+        (bytes, 0, words, lines, max_line_length)
+    } else {
+        binary_file(
+            path,
+            !config.words && !config.lines && !config.max_line_length,
+            config.no_decompress,
+        )?
     };
     Ok(Count {
         context: Context::File { path },
         bytes: if config.bytes { Some(bytes) } else { None },
-        chars: if config.chars { Some(chars) } else { None },
+        chars: if config.chars && utf8_decodable {
+            Some(chars)
+        } else {
+            None
+        },
         words: if config.words { Some(words) } else { None },
         lines: if config.lines { Some(lines) } else { None },
+        content_type: if config.chars { content_type } else { None },
+        max_line_length: if config.max_line_length {
+            Some(max_line_length)
+        } else {
+            None
+        },
     })
 }
 
@@ -256,6 +564,9 @@ mod tests {
             chars: true,
             words: true,
             lines: true,
+            max_line_length: true,
+            jobs: None,
+            no_decompress: false,
         }
     }
 
@@ -265,6 +576,9 @@ mod tests {
             chars: false,
             words: false,
             lines: false,
+            max_line_length: false,
+            jobs: None,
+            no_decompress: false,
         }
     }
 
@@ -275,6 +589,8 @@ mod tests {
             chars: None,
             words: None,
             lines: None,
+            content_type: None,
+            max_line_length: None,
         }
     }
 
@@ -285,6 +601,8 @@ mod tests {
             chars: Some(726780),
             words: Some(183155),
             lines: Some(20681),
+            content_type: Some(ContentType::Utf8),
+            max_line_length: Some(154),
         }
     }
 
@@ -332,6 +650,7 @@ mod tests {
             count.unwrap(),
             Count {
                 chars: Some(726780),
+                content_type: Some(ContentType::Utf8),
                 ..count_empty(&path)
             }
         );
@@ -375,12 +694,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_counts_max_line_length_in_file() {
+        let path: PathBuf = default_file_path();
+        let count = file(
+            &path,
+            &Config {
+                max_line_length: true,
+                ..config_all_false()
+            },
+        );
+        assert_eq!(
+            count.unwrap(),
+            Count {
+                max_line_length: Some(154),
+                ..count_empty(&path)
+            }
+        );
+    }
+
+    #[test]
+    fn update_line_width_rounds_tabs_to_next_stop() {
+        let (mut running_width, mut max_width) = (0, 0);
+        for c in "a\tbb\t".chars() {
+            update_line_width(c, &mut running_width, &mut max_width);
+        }
+        assert_eq!(16, running_width);
+    }
+
+    #[test]
+    fn is_zero_width_matches_combining_marks() {
+        assert!(is_zero_width(0x0301)); // combining acute accent
+        assert!(!is_zero_width('a' as u32));
+    }
+
+    #[test]
+    fn is_east_asian_wide_matches_cjk_and_not_ascii() {
+        assert!(is_east_asian_wide(0x4E2D)); // 中
+        assert!(!is_east_asian_wide('a' as u32));
+    }
+
+    #[test]
+    fn utf8_reader_counts_multibyte_display_width_correctly() {
+        let text = "é\n".as_bytes();
+        let reader = BufReader::with_capacity(10, text);
+        let (_, _, _, _, max_line_length) = utf8(reader);
+        assert_eq!(1, max_line_length);
+    }
+
+    #[test]
+    fn utf8_reader_counts_east_asian_wide_and_zero_width_in_line() {
+        let text = "中\u{0301}\n".as_bytes();
+        let reader = BufReader::with_capacity(10, text);
+        let (_, chars, _, _, max_line_length) = utf8(reader);
+        assert_eq!(3, chars);
+        assert_eq!(2, max_line_length);
+    }
+
     #[test]
     fn binary_reader_has_correct_counts() {
         let text: &[u8] =
             "hello???????????????????????????????????????????????? hello world 12345\n67890???? ???? ???? ????".as_bytes();
         let reader = BufReader::with_capacity(10, text);
-        let (bytes, words, lines) = binary(reader);
+        let (bytes, words, lines, max_line_length) = binary(reader);
         assert_eq!(
             96, bytes,
             "expected byte count does not match actual byte count"
@@ -393,6 +769,10 @@ mod tests {
             1, lines,
             "expected line count does not match actual line count"
         );
+        assert_eq!(
+            71, max_line_length,
+            "expected max line length does not match actual max line length"
+        );
     }
 
     #[test]
@@ -400,7 +780,7 @@ mod tests {
         let text: &[u8] =
             "hello???????????????????????????????????????????????? hello world 12345\n67890???? ???? ???? ????".as_bytes();
         let reader = BufReader::with_capacity(10, text);
-        let (bytes, chars, words, lines) = utf8(reader);
+        let (bytes, chars, words, lines, max_line_length) = utf8(reader);
         assert_eq!(
             96, bytes,
             "expected byte count does not match actual byte count"
@@ -417,6 +797,10 @@ mod tests {
             1, lines,
             "expected line count does not match actual line count"
         );
+        assert_eq!(
+            71, max_line_length,
+            "expected max line length does not match actual max line length"
+        );
     }
 
     #[test]
@@ -424,7 +808,7 @@ mod tests {
         let text: &[u8] =
             "hello???????????????????????????????????????????????? hello world 12345\n67890???? ???? ???? ????".as_bytes();
         let reader = BufReader::with_capacity(10, text);
-        let (bytes, lines) = hyperscreamingcount(reader);
+        let (bytes, lines, max_line_length) = hyperscreamingcount(reader);
         assert_eq!(
             96, bytes,
             "expected byte count does not match actual byte count"
@@ -433,5 +817,247 @@ mod tests {
             1, lines,
             "expected line count does not match actual line count"
         );
+        assert_eq!(
+            71, max_line_length,
+            "expected max line length does not match actual max line length"
+        );
+    }
+
+    #[test]
+    fn files_preserves_input_order_when_jobs_is_capped() {
+        let path = default_file_path();
+        let paths = vec![path.clone(), path.clone(), path.clone()];
+        let config = Config {
+            jobs: Some(2),
+            ..config_all_true()
+        };
+        let results = files(&paths, &config);
+        assert_eq!(3, results.len());
+        for result in results {
+            assert_eq!(count_for_default_file(&path), result.unwrap());
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tc_test_{}_{}", std::process::id(), name))
+    }
+
+    fn write_gzip_member(path: &PathBuf, contents: &[u8], append: bool) {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .unwrap();
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn is_gzip_detects_extension_and_magic_bytes() {
+        let gz_path = temp_path("is_gzip.txt.gz");
+        write_gzip_member(&gz_path, b"hello world\n", false);
+        assert!(is_gzip(&gz_path));
+
+        let plain_path = temp_path("is_gzip_plain.txt");
+        std::fs::write(&plain_path, b"hello world\n").unwrap();
+        assert!(!is_gzip(&plain_path));
+
+        std::fs::remove_file(&gz_path).unwrap();
+        std::fs::remove_file(&plain_path).unwrap();
+    }
+
+    #[test]
+    fn open_possibly_gzipped_decompresses_multi_member_streams() {
+        let path = temp_path("multi_stream.gz");
+        write_gzip_member(&path, b"hello ", false);
+        write_gzip_member(&path, b"world\n", true);
+
+        let mut reader = open_possibly_gzipped(&path, false).unwrap();
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        assert_eq!("hello world\n", out);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_possibly_gzipped_honors_no_decompress() {
+        let path = temp_path("no_decompress.gz");
+        write_gzip_member(&path, b"hello world\n", false);
+
+        let mut reader = open_possibly_gzipped(&path, true).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_ne!(b"hello world\n".to_vec(), out);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn inspect_content_type_classifies_utf8_utf16_and_binary() {
+        assert_eq!(
+            ContentType::Utf8,
+            inspect_content_type(b"hello world\n", true)
+        );
+        assert_eq!(
+            ContentType::Utf8,
+            inspect_content_type(&[0xEF, 0xBB, 0xBF, b'h', b'i'], true)
+        );
+        assert_eq!(
+            ContentType::Utf16,
+            inspect_content_type(&[0xFF, 0xFE, b'h', 0x00], true)
+        );
+        assert_eq!(
+            ContentType::Binary,
+            inspect_content_type(&[b'h', b'i', 0x00, b'!'], true)
+        );
+        assert_eq!(
+            ContentType::Binary,
+            inspect_content_type(&[0x68, 0x69, 0x80, 0x81], true)
+        );
+    }
+
+    #[test]
+    fn inspect_content_type_forgives_multibyte_char_split_by_the_sniff_window() {
+        // "中" (e4 b8 ad) straddling the end of a non-final sniff read.
+        let truncated = [b'a', b'b', 0xE4, 0xB8];
+        assert_eq!(
+            ContentType::Utf8,
+            inspect_content_type(&truncated, false),
+            "a sniff-window boundary should forgive a trailing partial char"
+        );
+        assert_eq!(
+            ContentType::Binary,
+            inspect_content_type(&truncated, true),
+            "the same bytes at true EOF are a genuinely truncated file, not UTF-8"
+        );
+    }
+
+    #[test]
+    fn to_counts_vec_keeps_a_slot_for_none_fields() {
+        let path: PathBuf = ["a.txt"].iter().collect();
+        let count = Count {
+            chars: None,
+            ..count_for_default_file(&path)
+        };
+        let config = config_all_true();
+        assert_eq!(
+            vec![Some(1048697), None, Some(183155), Some(20681), Some(154)],
+            count.to_counts_vec(&config)
+        );
+        assert_eq!(
+            vec![
+                path.to_string_lossy().to_string(),
+                "1048697".to_string(),
+                "-".to_string(),
+                "183155".to_string(),
+                "20681".to_string(),
+                "154".to_string(),
+            ],
+            count.to_str_vec(&config)
+        );
+    }
+
+    #[test]
+    fn detect_content_type_does_not_misclassify_a_file_truncated_at_true_eof() {
+        let path = temp_path("truncated_multibyte.txt");
+        std::fs::write(&path, [b'a', b'b', 0xE4, 0xB8]).unwrap();
+
+        assert_eq!(
+            ContentType::Binary,
+            detect_content_type(&path, false).unwrap()
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mmap_eligible_requires_size_threshold_and_skips_gzip() {
+        let small_path = temp_path("mmap_small.txt");
+        std::fs::write(&small_path, b"hello world\n").unwrap();
+        let config = config_all_true();
+        assert!(!mmap_eligible(&small_path, &config));
+
+        let large_path = temp_path("mmap_large.txt");
+        let contents = vec![b'a'; (MMAP_THRESHOLD as usize) + 1];
+        std::fs::write(&large_path, &contents).unwrap();
+        assert!(mmap_eligible(&large_path, &config));
+
+        // Magic bytes plus padding past the threshold: is_gzip only sniffs the
+        // header, so this doesn't need to be a real decodable gzip stream.
+        let gz_path = temp_path("mmap_large.gz");
+        let mut gz_contents = GZIP_MAGIC.to_vec();
+        gz_contents.extend(contents.iter());
+        std::fs::write(&gz_path, &gz_contents).unwrap();
+        assert!(!mmap_eligible(&gz_path, &config));
+        assert!(mmap_eligible(
+            &gz_path,
+            &Config {
+                no_decompress: true,
+                ..config_all_true()
+            }
+        ));
+
+        std::fs::remove_file(&small_path).unwrap();
+        std::fs::remove_file(&large_path).unwrap();
+        std::fs::remove_file(&gz_path).unwrap();
+    }
+
+    #[test]
+    fn mmap_file_matches_the_streaming_binary_counts() {
+        let path = temp_path("mmap_equivalence.txt");
+        let mut contents = vec![b'x'; (MMAP_THRESHOLD as usize) + 1];
+        contents.extend_from_slice(b"\nhello world\n");
+        std::fs::write(&path, &contents).unwrap();
+
+        let (mmap_bytes, mmap_words, mmap_lines, mmap_max_width) = mmap_file(&path).unwrap();
+        let (stream_bytes, _, stream_words, stream_lines, stream_max_width) =
+            binary_file(&path, false, false).unwrap();
+
+        assert_eq!(stream_bytes, mmap_bytes);
+        assert_eq!(stream_words, mmap_words);
+        assert_eq!(stream_lines, mmap_lines);
+        assert_eq!(stream_max_width, mmap_max_width);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn total_sums_counts_and_maxes_max_line_length() {
+        let path_a: PathBuf = ["a.txt"].iter().collect();
+        let path_b: PathBuf = ["b.txt"].iter().collect();
+        let a = Count {
+            context: Context::File { path: &path_a },
+            bytes: Some(10),
+            chars: Some(8),
+            words: Some(2),
+            lines: Some(1),
+            max_line_length: Some(9),
+            content_type: Some(ContentType::Utf8),
+        };
+        let b = Count {
+            context: Context::File { path: &path_b },
+            bytes: Some(20),
+            chars: None,
+            words: Some(3),
+            lines: Some(4),
+            max_line_length: Some(15),
+            content_type: None,
+        };
+        let total = Count::total(&[&a, &b]);
+        assert_eq!(Context::Total, total.context);
+        assert_eq!(Some(30), total.bytes);
+        assert_eq!(Some(8), total.chars);
+        assert_eq!(Some(5), total.words);
+        assert_eq!(Some(5), total.lines);
+        assert_eq!(Some(15), total.max_line_length);
+        assert_eq!(None, total.content_type);
+        assert_eq!("total", total.groupname());
     }
 }
@@ -1,5 +1,5 @@
 use std::fs::{metadata, File};
-use std::io::{BufReader, Read, Stdin};
+use std::io::{BufRead, BufReader, Read, Stdin};
 use std::path::PathBuf;
 
 mod config;
@@ -18,6 +18,7 @@ pub struct Counted {
     pub chars: Option<usize>,
     pub words: Option<usize>,
     pub lines: Option<usize>,
+    pub max_line_length: Option<usize>,
 }
 
 pub trait Count<T> {
@@ -36,6 +37,8 @@ impl Countable<Stdin> for Stdin {
             chars: false,
             words: false,
             lines: false,
+            max_line_length: false,
+            no_decompress: false,
         }
     }
 }
@@ -47,6 +50,8 @@ impl Countable<File> for File {
             chars: false,
             words: false,
             lines: false,
+            max_line_length: false,
+            no_decompress: false,
         }
     }
 }
@@ -58,6 +63,8 @@ impl Countable<PathBuf> for PathBuf {
             chars: false,
             words: false,
             lines: false,
+            max_line_length: false,
+            no_decompress: false,
         }
     }
 }
@@ -69,26 +76,44 @@ pub struct Counter<T> {
     chars: bool,
     words: bool,
     lines: bool,
+    max_line_length: bool,
+    no_decompress: bool,
 }
 
 impl Count<PathBuf> for Counter<PathBuf> {
     fn count(self) -> Counted {
-        if self.bytes && !(self.chars || self.words || self.lines) {
+        let is_gzip = !self.no_decompress && count::is_gzip(&self.data);
+        if self.bytes
+            && !(self.chars || self.words || self.lines || self.max_line_length)
+            && !is_gzip
+        {
             let bytes = metadata(self.data).unwrap().len() as usize; // TODO
             Counted {
                 bytes: Some(bytes),
                 chars: None,
                 words: None,
                 lines: None,
+                max_line_length: None,
             }
         } else {
-            count_readable(Counter {
-                data: File::open(self.data).unwrap(), // TODO
+            // delegates to count::file rather than duplicating its content-type/mmap-eligibility logic
+            let config = Config {
                 bytes: self.bytes,
                 chars: self.chars,
                 words: self.words,
                 lines: self.lines,
-            })
+                max_line_length: self.max_line_length,
+                jobs: None,
+                no_decompress: self.no_decompress,
+            };
+            let count = count::file(&self.data, &config).unwrap(); // TODO
+            Counted {
+                bytes: count.bytes,
+                chars: count.chars,
+                words: count.words,
+                lines: count.lines,
+                max_line_length: count.max_line_length,
+            }
         }
     }
 }
@@ -130,35 +155,83 @@ impl<T> Counter<T> {
             ..self
         }
     }
+    fn max_line_length(self) -> Counter<T> {
+        Counter {
+            max_line_length: true,
+            ..self
+        }
+    }
+    fn no_decompress(self) -> Counter<T> {
+        Counter {
+            no_decompress: true,
+            ..self
+        }
+    }
 }
 
 fn count_readable<R: Read>(counter: Counter<R>) -> Counted {
-    if counter.chars {
-        let reader = BufReader::with_capacity(BUFFER_SIZE, counter.data);
-        let (bytes, chars, words, lines) = count::utf8(reader);
+    // max_line_length needs decoded Unicode scalars for its display-width
+    // math, so it takes the UTF-8 detection path too, not just chars.
+    if counter.chars || counter.max_line_length {
+        let mut reader = BufReader::with_capacity(BUFFER_SIZE, counter.data);
+        let is_binary = reader
+            .fill_buf()
+            .map(|buf| count::inspect_content_type(buf, buf.len() < BUFFER_SIZE))
+            .map(|content_type| content_type == count::ContentType::Binary)
+            .unwrap_or(false);
+        if is_binary {
+            let (bytes, words, lines, max_line_length) = count::binary(reader);
+            return Counted {
+                bytes: if counter.bytes { Some(bytes) } else { None },
+                chars: None,
+                words: if counter.words { Some(words) } else { None },
+                lines: if counter.lines { Some(lines) } else { None },
+                max_line_length: if counter.max_line_length {
+                    Some(max_line_length)
+                } else {
+                    None
+                },
+            };
+        }
+        let (bytes, chars, words, lines, max_line_length) = count::utf8(reader);
         Counted {
             bytes: if counter.bytes { Some(bytes) } else { None },
             chars: if counter.chars { Some(chars) } else { None },
             words: if counter.words { Some(words) } else { None },
             lines: if counter.lines { Some(lines) } else { None },
+            max_line_length: if counter.max_line_length {
+                Some(max_line_length)
+            } else {
+                None
+            },
         }
     } else if counter.lines && !counter.words {
         let reader = BufReader::with_capacity(BUFFER_SIZE, counter.data);
-        let (bytes, lines) = count::hyperscreamingcount(reader);
+        let (bytes, lines, max_line_length) = count::hyperscreamingcount(reader);
         Counted {
             bytes: if counter.bytes { Some(bytes) } else { None },
             chars: None,
             words: None,
             lines: if counter.lines { Some(lines) } else { None },
+            max_line_length: if counter.max_line_length {
+                Some(max_line_length)
+            } else {
+                None
+            },
         }
     } else {
         let reader = BufReader::with_capacity(BUFFER_SIZE, counter.data);
-        let (bytes, words, lines) = count::binary(reader);
+        let (bytes, words, lines, max_line_length) = count::binary(reader);
         Counted {
             bytes: if counter.bytes { Some(bytes) } else { None },
             chars: None,
             words: if counter.words { Some(words) } else { None },
             lines: if counter.lines { Some(lines) } else { None },
+            max_line_length: if counter.max_line_length {
+                Some(max_line_length)
+            } else {
+                None
+            },
         }
     }
 }
@@ -170,18 +243,18 @@ mod tests {
     fn correct_defaults_for_counter() {
         let c = std::io::stdin().countable();
         assert_eq!(
-            (false, false, false, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, false, false, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
         let c = File::open("src/lib.rs").unwrap().countable();
         assert_eq!(
-            (false, false, false, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, false, false, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
         let c = PathBuf::new().countable();
         assert_eq!(
-            (false, false, false, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, false, false, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
     }
 
@@ -189,23 +262,28 @@ mod tests {
     fn correct_counter_changes() {
         let c = PathBuf::new().countable().bytes();
         assert_eq!(
-            (true, false, false, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (true, false, false, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
         let c = PathBuf::new().countable().chars();
         assert_eq!(
-            (false, true, false, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, true, false, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
         let c = PathBuf::new().countable().words();
         assert_eq!(
-            (false, false, true, false),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, false, true, false, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
         let c = PathBuf::new().countable().lines();
         assert_eq!(
-            (false, false, false, true),
-            (c.bytes, c.chars, c.words, c.lines)
+            (false, false, false, true, false),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
+        );
+        let c = PathBuf::new().countable().max_line_length();
+        assert_eq!(
+            (false, false, false, false, true),
+            (c.bytes, c.chars, c.words, c.lines, c.max_line_length)
         );
     }
 
@@ -1,6 +1,9 @@
 use colored::*;
 use num_format::{Locale, ToFormattedString};
-use std::io::stdin;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{stdin, Read};
+use std::os::unix::ffi::OsStrExt;
 use std::path::PathBuf;
 use structopt::StructOpt;
 use term_table::row::Row;
@@ -21,23 +24,35 @@ struct Cli {
     #[structopt(short, long)]
     lines: bool,
 
+    #[structopt(short = "L", long = "max-line-length")]
+    max_line_length: bool,
+
     #[structopt(long)]
-    stdin: bool,
+    jobs: Option<usize>,
+
+    #[structopt(long)]
+    no_decompress: bool,
 
     #[structopt(long)]
-    file_from: PathBuf,
+    stdin: bool,
+
+    #[structopt(long = "files0-from")]
+    files0_from: Option<PathBuf>,
 
     files: Vec<PathBuf>,
 }
 
 impl From<&Cli> for tc::Config {
     fn from(cli: &Cli) -> Self {
-        if !(cli.bytes || cli.chars || cli.words || cli.lines) {
+        if !(cli.bytes || cli.chars || cli.words || cli.lines || cli.max_line_length) {
             tc::Config {
                 bytes: true,
                 chars: true,
                 words: true,
                 lines: true,
+                max_line_length: false,
+                jobs: cli.jobs,
+                no_decompress: cli.no_decompress,
             }
         } else {
             tc::Config {
@@ -45,6 +60,9 @@ impl From<&Cli> for tc::Config {
                 chars: cli.chars,
                 words: cli.words,
                 lines: cli.lines,
+                max_line_length: cli.max_line_length,
+                jobs: cli.jobs,
+                no_decompress: cli.no_decompress,
             }
         }
     }
@@ -70,6 +88,20 @@ fn read_files_stdin() -> Vec<PathBuf> {
     paths
 }
 
+// reads a NUL-separated path list from `source` ("-" means stdin), matching GNU wc's --files0-from
+fn read_files0_from(source: &PathBuf) -> Vec<PathBuf> {
+    let mut buf = Vec::new();
+    if source.as_os_str() == "-" {
+        stdin().lock().read_to_end(&mut buf).unwrap();
+    } else {
+        File::open(source).unwrap().read_to_end(&mut buf).unwrap();
+    }
+    buf.split(|&b| b == 0)
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| PathBuf::from(OsStr::from_bytes(chunk)))
+        .collect()
+}
+
 fn pprint(results: Vec<Result<tc::count::Count, tc::count::Error>>, config: &tc::Config) {
     let mut table = Table::new();
     table.style = term_table::TableStyle::thin();
@@ -81,9 +113,18 @@ fn pprint(results: Vec<Result<tc::count::Count, tc::count::Error>>, config: &tc:
             TableCell::new_with_alignment(s.blue().bold().underline(), 1, Alignment::Center)
         }),
     );
+    if config.chars {
+        headers.push(TableCell::new_with_alignment(
+            "type".blue().bold().underline(),
+            1,
+            Alignment::Center,
+        ));
+    }
     table.add_row(Row::new(headers));
+    let mut oks: Vec<&tc::count::Count> = Vec::new();
     for res in &results {
         if let Ok(counts) = res {
+            oks.push(counts);
             let mut cells: Vec<TableCell> = Vec::new();
             cells.reserve_exact(results.len() + 1);
             cells.push(TableCell::new_with_alignment(
@@ -91,16 +132,44 @@ fn pprint(results: Vec<Result<tc::count::Count, tc::count::Error>>, config: &tc:
                 1,
                 Alignment::Left,
             ));
-            cells.extend(counts.to_counts_vec().iter().map(|count| {
+            cells.extend(counts.to_counts_vec(config).iter().map(|count| {
                 TableCell::new_with_alignment(
-                    count.to_formatted_string(&Locale::en),
+                    count.map_or_else(|| "-".to_string(), |c| c.to_formatted_string(&Locale::en)),
                     1,
                     Alignment::Right,
                 )
             }));
+            if let Some(content_type) = counts.content_type {
+                cells.push(TableCell::new_with_alignment(
+                    content_type.as_str(),
+                    1,
+                    Alignment::Center,
+                ));
+            }
             table.add_row(Row::new(cells));
         }
     }
+    if oks.len() > 1 {
+        let total = tc::count::Count::total(&oks);
+        let mut cells: Vec<TableCell> = Vec::new();
+        cells.reserve_exact(results.len() + 1);
+        cells.push(TableCell::new_with_alignment(
+            total.groupname().yellow().bold().underline(),
+            1,
+            Alignment::Left,
+        ));
+        cells.extend(total.to_counts_vec(config).iter().map(|count| {
+            TableCell::new_with_alignment(
+                count.map_or_else(|| "-".to_string(), |c| c.to_formatted_string(&Locale::en)),
+                1,
+                Alignment::Right,
+            )
+        }));
+        if config.chars {
+            cells.push(TableCell::new_with_alignment("", 1, Alignment::Center));
+        }
+        table.add_row(Row::new(cells));
+    }
     println!("{}", table.render());
 }
 
@@ -108,7 +177,9 @@ fn run() -> Result<(), tc::error::Error> {
     let cli = Cli::from_args();
     let config = tc::Config::from(&cli);
     let mut files = cli.files;
-    if files.len() == 0 && !cli.stdin {
+    if let Some(source) = &cli.files0_from {
+        files.extend(read_files0_from(source));
+    } else if files.len() == 0 && !cli.stdin {
         files.extend(read_files_stdin());
     }
     let results = tc::count::files(&files, &config);
@@ -122,3 +193,30 @@ fn main() {
         Err(_) => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_files0_from_splits_on_nul_and_skips_empty_chunks() {
+        let path = std::env::temp_dir().join(format!(
+            "tc_test_{}_read_files0_from.list",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"a.txt\0b\nwith\nnewlines.txt\0\0c.txt\0").unwrap();
+
+        let files = read_files0_from(&path);
+
+        assert_eq!(
+            vec![
+                PathBuf::from("a.txt"),
+                PathBuf::from("b\nwith\nnewlines.txt"),
+                PathBuf::from("c.txt"),
+            ],
+            files
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}